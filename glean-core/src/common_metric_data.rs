@@ -1,7 +1,11 @@
+use serde::{Deserialize, Serialize};
+
 use super::Glean;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum Lifetime {
     /// The metric is reset with each sent ping
+    #[default]
     Ping,
     /// The metric is reset on application restart
     Application,
@@ -9,13 +13,18 @@ pub enum Lifetime {
     User
 }
 
-impl Default for Lifetime {
-    fn default() -> Self {
-        Lifetime::Ping
+impl Lifetime {
+    /// The string used to namespace this lifetime's entries within the storage backend.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Lifetime::Ping => "ping",
+            Lifetime::Application => "application",
+            Lifetime::User => "user",
+        }
     }
 }
 
-#[derive(Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct CommonMetricData {
     pub name: String,
     pub category: String,
@@ -30,11 +39,17 @@ impl CommonMetricData {
     }
 
     pub fn should_record(&self) -> bool {
-        if self.disabled || !Glean::singleton().is_upload_enabled() {
+        let glean = Glean::singleton();
+        if !glean.is_upload_enabled() {
             return false;
         }
 
-        return true;
+        // A remote server-knobs override takes precedence over the
+        // compiled-in `disabled` flag, so operators can disable a noisy or
+        // sensitive metric without shipping a new build.
+        glean
+            .metric_enabled_override(&self.fullname())
+            .unwrap_or(!self.disabled)
     }
 
     pub fn storage_names(&self) -> &[String] {
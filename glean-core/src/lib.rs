@@ -0,0 +1,202 @@
+//! glean-core: the language-agnostic core of the Glean SDK.
+
+mod common_metric_data;
+pub mod metrics;
+pub mod ping;
+pub mod registry;
+pub mod storage;
+pub mod upload;
+
+pub use common_metric_data::{CommonMetricData, Lifetime};
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use once_cell::sync::OnceCell;
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use ping::PingMaker;
+use storage::StorageManager;
+use upload::{PingRequest, UploadManager};
+
+static GLEAN: OnceCell<Glean> = OnceCell::new();
+
+/// The central Glean object, shared by every metric recorded in the process.
+///
+/// A single instance lives behind a process-wide singleton, set up once via
+/// [`Glean::initialize`] and reached afterwards through [`Glean::singleton`].
+pub struct Glean {
+    upload_enabled: AtomicBool,
+    storage: StorageManager,
+    upload: UploadManager,
+    /// Remote overrides of a metric's enabled state, keyed by `fullname()`,
+    /// applied via [`Glean::apply_server_knobs_config`].
+    server_knobs: Mutex<HashMap<String, bool>>,
+}
+
+impl Glean {
+    fn new(data_path: PathBuf) -> Self {
+        Glean {
+            upload_enabled: AtomicBool::new(true),
+            storage: StorageManager::new(data_path),
+            upload: UploadManager::new(),
+            server_knobs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set up the global Glean instance, rooted at `data_path` on disk.
+    ///
+    /// Subsequent calls are no-ops: Glean is only ever initialized once per process.
+    pub fn initialize(data_path: impl Into<PathBuf>) -> &'static Glean {
+        GLEAN.get_or_init(|| Glean::new(data_path.into()))
+    }
+
+    /// Access the process-wide Glean instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Glean::initialize`] has not been called yet.
+    pub fn singleton() -> &'static Glean {
+        GLEAN
+            .get()
+            .expect("Glean::singleton called before Glean::initialize")
+    }
+
+    /// Whether telemetry upload is currently enabled.
+    pub fn is_upload_enabled(&self) -> bool {
+        self.upload_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enable or disable telemetry upload for the whole process.
+    pub fn set_upload_enabled(&self, enabled: bool) {
+        self.upload_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// The lifetime-aware storage backend for recorded metric values.
+    pub fn storage(&self) -> &StorageManager {
+        &self.storage
+    }
+
+    /// The queue of assembled pings awaiting upload.
+    pub fn upload_queue(&self) -> &UploadManager {
+        &self.upload
+    }
+
+    /// Assemble and queue the named ping for upload.
+    ///
+    /// Returns `false` if there was no data to send (or upload is disabled),
+    /// in which case no ping was queued and `Ping`-lifetime data was left untouched.
+    pub fn submit_ping(&self, ping_name: &str) -> bool {
+        let payload = match PingMaker::new().collect(self, ping_name) {
+            Some(payload) => payload,
+            None => return false,
+        };
+
+        self.upload.enqueue(PingRequest {
+            document_id: Uuid::new_v4().to_string(),
+            ping_name: ping_name.to_string(),
+            payload,
+        });
+        self.storage.clear_ping(ping_name);
+        ping::reset_ping_start_time(self, ping_name);
+        true
+    }
+
+    /// Replace the whole server-knobs configuration with the overrides in `config`.
+    ///
+    /// `config` is expected to have the shape `{"metrics_enabled": {"<fullname>": bool}}`.
+    /// The replacement is atomic: the previous overrides are entirely discarded, so a
+    /// partial or stale update can never linger alongside the new one.
+    pub fn apply_server_knobs_config(&self, config: &JsonValue) {
+        let mut overrides = HashMap::new();
+        if let Some(metrics_enabled) = config.get("metrics_enabled").and_then(JsonValue::as_object) {
+            for (fullname, enabled) in metrics_enabled {
+                if let Some(enabled) = enabled.as_bool() {
+                    overrides.insert(fullname.clone(), enabled);
+                }
+            }
+        }
+        *self.server_knobs.lock().unwrap() = overrides;
+    }
+
+    /// Discard every server-knobs override, reverting all metrics to their compiled-in
+    /// `disabled` value.
+    pub fn reset_server_knobs_config(&self) {
+        self.server_knobs.lock().unwrap().clear();
+    }
+
+    /// The server-knobs override for `fullname`, if one has been applied.
+    pub(crate) fn metric_enabled_override(&self, fullname: &str) -> Option<bool> {
+        self.server_knobs.lock().unwrap().get(fullname).copied()
+    }
+}
+
+/// Test-only access to the process-wide Glean singleton, backed by a
+/// throwaway temp directory shared by this crate's unit tests.
+///
+/// Every metric type's tests need a live `Glean::singleton()` to record
+/// into, but `Glean::initialize` can only be called once per process, so
+/// this lazily initializes it on first use instead of each test doing so.
+#[cfg(test)]
+pub(crate) fn test_glean() -> &'static Glean {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let dir = std::env::temp_dir().join(format!("glean_core_test_{}", std::process::id()));
+        Glean::initialize(dir);
+    });
+    Glean::singleton()
+}
+
+/// Serializes tests that touch process-wide state on the shared [`test_glean`]
+/// singleton (upload-enabled, server-knobs overrides), so one test flipping
+/// those flags can't race another test's assertions.
+#[cfg(test)]
+pub(crate) fn test_lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn meta(name: &str, disabled: bool) -> CommonMetricData {
+        CommonMetricData {
+            name: name.to_string(),
+            category: "test.server_knobs".to_string(),
+            send_in_pings: vec!["metrics".to_string()],
+            lifetime: Lifetime::Ping,
+            disabled,
+        }
+    }
+
+    #[test]
+    fn server_knobs_override_takes_precedence_over_disabled() {
+        let _guard = test_lock();
+        let glean = test_glean();
+        glean.set_upload_enabled(true);
+        glean.reset_server_knobs_config();
+
+        let compiled_enabled = meta("compiled_enabled", false);
+        let compiled_disabled = meta("compiled_disabled", true);
+
+        glean.apply_server_knobs_config(&json!({
+            "metrics_enabled": {
+                compiled_enabled.fullname(): false,
+                compiled_disabled.fullname(): true,
+            }
+        }));
+
+        assert!(!compiled_enabled.should_record());
+        assert!(compiled_disabled.should_record());
+
+        glean.reset_server_knobs_config();
+        assert!(compiled_enabled.should_record());
+        assert!(!compiled_disabled.should_record());
+    }
+}
@@ -0,0 +1,89 @@
+use serde_json::json;
+
+use crate::{CommonMetricData, Glean};
+
+/// A metric that accumulates a running count, independently per ping.
+pub struct CounterMetric {
+    meta: CommonMetricData,
+}
+
+impl CounterMetric {
+    pub fn new(meta: CommonMetricData) -> Self {
+        CounterMetric { meta }
+    }
+
+    /// Add `amount` to the counter's current value in every ping it is sent in.
+    pub fn add(&self, amount: i64) {
+        if !self.meta.should_record() {
+            return;
+        }
+
+        let glean = Glean::singleton();
+        for ping_name in self.meta.storage_names() {
+            let current = glean
+                .storage()
+                .get(ping_name, self.meta.lifetime, &self.meta.fullname())
+                .and_then(|value| value.as_i64())
+                .unwrap_or(0);
+            glean
+                .storage()
+                .record_one(ping_name, &self.meta, "counter", json!(current + amount));
+        }
+    }
+
+    /// The counter's current value within `ping_name`, if anything has been recorded.
+    #[cfg(test)]
+    pub fn get_value(&self, ping_name: &str) -> Option<i64> {
+        Glean::singleton()
+            .storage()
+            .get(ping_name, self.meta.lifetime, &self.meta.fullname())
+            .and_then(|value| value.as_i64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_glean, test_lock};
+
+    fn metric(name: &str) -> CounterMetric {
+        CounterMetric::new(CommonMetricData {
+            name: name.to_string(),
+            category: "test.counter".to_string(),
+            send_in_pings: vec!["metrics".to_string(), "baseline".to_string()],
+            disabled: false,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn add_accumulates_independently_per_ping() {
+        let _guard = test_lock();
+        test_glean();
+        let metric = metric("accumulates");
+
+        metric.add(3);
+        metric.add(4);
+
+        assert_eq!(metric.get_value("metrics"), Some(7));
+        assert_eq!(metric.get_value("baseline"), Some(7));
+    }
+
+    #[test]
+    fn disabled_metric_is_not_recorded() {
+        let _guard = test_lock();
+        test_glean();
+        let meta = CommonMetricData {
+            name: "disabled".to_string(),
+            category: "test.counter".to_string(),
+            send_in_pings: vec!["metrics".to_string()],
+            disabled: true,
+            ..Default::default()
+        };
+        let metric = CounterMetric::new(meta);
+
+        metric.add(1);
+
+        assert_eq!(metric.get_value("metrics"), None);
+    }
+}
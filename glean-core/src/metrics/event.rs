@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value as JsonValue};
+
+use crate::ping::ping_start_time_millis;
+use crate::{CommonMetricData, Glean};
+
+/// A metric that records discrete events, accumulated as an ordered list
+/// within each ping it is sent in.
+pub struct EventMetric {
+    meta: CommonMetricData,
+    allowed_extra_keys: Vec<String>,
+}
+
+impl EventMetric {
+    pub fn new(meta: CommonMetricData, allowed_extra_keys: Vec<String>) -> Self {
+        EventMetric {
+            meta,
+            allowed_extra_keys,
+        }
+    }
+
+    /// Record this event, along with a set of string extra keys.
+    ///
+    /// Extra keys that aren't in `allowed_extra_keys` are rejected and logged
+    /// rather than stored; the rest of the event is still recorded. Each
+    /// event is timestamped in milliseconds relative to the start of its
+    /// ping's current collection period.
+    pub fn record(&self, extra: HashMap<String, String>) {
+        if !self.meta.should_record() {
+            return;
+        }
+
+        let mut accepted_extra = serde_json::Map::new();
+        for (key, value) in extra {
+            if self.allowed_extra_keys.iter().any(|allowed| allowed == &key) {
+                accepted_extra.insert(key, json!(value));
+            } else {
+                log::warn!(
+                    "Ignoring unknown extra key '{}' for event {}",
+                    key,
+                    self.meta.fullname()
+                );
+            }
+        }
+
+        let glean = Glean::singleton();
+        for ping_name in self.meta.storage_names() {
+            let timestamp = chrono::Utc::now().timestamp_millis() - ping_start_time_millis(glean, ping_name);
+
+            let mut events: Vec<JsonValue> = glean
+                .storage()
+                .get(ping_name, self.meta.lifetime, &self.meta.fullname())
+                .and_then(|value| value.as_array().cloned())
+                .unwrap_or_default();
+            events.push(json!({
+                "timestamp": timestamp,
+                "extra": accepted_extra,
+            }));
+
+            glean
+                .storage()
+                .record_one(ping_name, &self.meta, "event", json!(events));
+        }
+    }
+
+    /// The ordered list of events recorded so far within `ping_name`.
+    #[cfg(test)]
+    pub fn get_value(&self, ping_name: &str) -> Option<Vec<JsonValue>> {
+        Glean::singleton()
+            .storage()
+            .get(ping_name, self.meta.lifetime, &self.meta.fullname())
+            .and_then(|value| value.as_array().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_glean, test_lock};
+
+    fn metric(name: &str, allowed_extra_keys: Vec<String>) -> EventMetric {
+        EventMetric::new(
+            CommonMetricData {
+                name: name.to_string(),
+                category: "test.event".to_string(),
+                send_in_pings: vec!["events".to_string()],
+                disabled: false,
+                ..Default::default()
+            },
+            allowed_extra_keys,
+        )
+    }
+
+    #[test]
+    fn events_accumulate_in_order() {
+        let _guard = test_lock();
+        test_glean();
+        let metric = metric("accumulates", vec!["button".to_string()]);
+
+        metric.record(HashMap::from([("button".to_string(), "back".to_string())]));
+        metric.record(HashMap::from([("button".to_string(), "forward".to_string())]));
+
+        let events = metric.get_value("events").expect("events were recorded");
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["extra"]["button"], "back");
+        assert_eq!(events[1]["extra"]["button"], "forward");
+    }
+
+    #[test]
+    fn unknown_extra_keys_are_dropped_not_the_whole_event() {
+        let _guard = test_lock();
+        test_glean();
+        let metric = metric("unknown_extra", vec!["known".to_string()]);
+
+        let mut extra = HashMap::new();
+        extra.insert("known".to_string(), "kept".to_string());
+        extra.insert("unknown".to_string(), "dropped".to_string());
+        metric.record(extra);
+
+        let events = metric.get_value("events").expect("event was recorded");
+        assert_eq!(events.len(), 1);
+        let recorded_extra = events[0]["extra"].as_object().unwrap();
+        assert_eq!(recorded_extra.get("known").unwrap(), "kept");
+        assert!(!recorded_extra.contains_key("unknown"));
+    }
+
+    #[test]
+    fn disabled_metric_is_not_recorded() {
+        let _guard = test_lock();
+        test_glean();
+        let metric = EventMetric::new(
+            CommonMetricData {
+                name: "disabled".to_string(),
+                category: "test.event".to_string(),
+                send_in_pings: vec!["events".to_string()],
+                disabled: true,
+                ..Default::default()
+            },
+            vec![],
+        );
+
+        metric.record(HashMap::new());
+
+        assert_eq!(metric.get_value("events"), None);
+    }
+}
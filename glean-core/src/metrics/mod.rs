@@ -0,0 +1,9 @@
+//! Concrete metric types layered on [`crate::CommonMetricData`].
+
+mod counter;
+mod event;
+mod string;
+
+pub use counter::CounterMetric;
+pub use event::EventMetric;
+pub use string::StringMetric;
@@ -0,0 +1,76 @@
+use serde_json::json;
+
+use crate::{CommonMetricData, Glean};
+
+/// A metric that stores a single string value.
+pub struct StringMetric {
+    meta: CommonMetricData,
+}
+
+impl StringMetric {
+    pub fn new(meta: CommonMetricData) -> Self {
+        StringMetric { meta }
+    }
+
+    /// Set the string value, replacing anything previously recorded, in every
+    /// ping it is sent in.
+    pub fn set(&self, value: impl Into<String>) {
+        if !self.meta.should_record() {
+            return;
+        }
+
+        Glean::singleton()
+            .storage()
+            .record(&self.meta, "string", json!(value.into()));
+    }
+
+    /// The string's current value within `ping_name`, if anything has been recorded.
+    #[cfg(test)]
+    pub fn get_value(&self, ping_name: &str) -> Option<String> {
+        Glean::singleton()
+            .storage()
+            .get(ping_name, self.meta.lifetime, &self.meta.fullname())
+            .and_then(|value| value.as_str().map(str::to_string))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_glean, test_lock};
+
+    #[test]
+    fn set_replaces_the_previous_value() {
+        let _guard = test_lock();
+        test_glean();
+        let metric = StringMetric::new(CommonMetricData {
+            name: "replaces".to_string(),
+            category: "test.string".to_string(),
+            send_in_pings: vec!["metrics".to_string()],
+            disabled: false,
+            ..Default::default()
+        });
+
+        metric.set("first");
+        metric.set("second");
+
+        assert_eq!(metric.get_value("metrics"), Some("second".to_string()));
+    }
+
+    #[test]
+    fn disabled_metric_is_not_recorded() {
+        let _guard = test_lock();
+        test_glean();
+        let metric = StringMetric::new(CommonMetricData {
+            name: "disabled".to_string(),
+            category: "test.string".to_string(),
+            send_in_pings: vec!["metrics".to_string()],
+            disabled: true,
+            ..Default::default()
+        });
+
+        metric.set("ignored");
+
+        assert_eq!(metric.get_value("metrics"), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! Assembling recorded metrics into a ping payload.
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value as JsonValue};
+
+use crate::{CommonMetricData, Glean, Lifetime};
+
+/// The ping that carries Glean's own bookkeeping metrics, such as per-ping sequence numbers.
+const GLEAN_INTERNAL_INFO_PING: &str = "glean_internal_info";
+
+/// Assembles the metrics recorded for a named ping into a JSON payload.
+#[derive(Default)]
+pub struct PingMaker;
+
+impl PingMaker {
+    pub fn new() -> Self {
+        PingMaker
+    }
+
+    /// Collect everything currently recorded for `ping_name` into a ping payload.
+    ///
+    /// Returns `None` if upload is disabled, or if there is no data to send -
+    /// submission should be a no-op in either case.
+    pub fn collect(&self, glean: &Glean, ping_name: &str) -> Option<JsonValue> {
+        if !glean.is_upload_enabled() {
+            return None;
+        }
+
+        let snapshot = glean.storage().snapshot(ping_name);
+        if snapshot.is_empty() {
+            return None;
+        }
+
+        let mut metrics: HashMap<String, HashMap<String, JsonValue>> = HashMap::new();
+        for (key, stored) in snapshot {
+            // `key` is "<lifetime>#<category.name>"; the payload only needs the metric's name.
+            let fullname = key.split_once('#').map_or(key.as_str(), |(_, name)| name).to_string();
+            metrics
+                .entry(stored.metric_type)
+                .or_default()
+                .insert(fullname, stored.value);
+        }
+
+        Some(json!({
+            "ping_info": self.ping_info(glean, ping_name),
+            "metrics": metrics,
+        }))
+    }
+
+    fn ping_info(&self, glean: &Glean, ping_name: &str) -> JsonValue {
+        let start_time = chrono::DateTime::from_timestamp_millis(ping_start_time_millis(glean, ping_name))
+            .unwrap_or_else(chrono::Utc::now)
+            .to_rfc3339();
+        let end_time = chrono::Utc::now().to_rfc3339();
+        json!({
+            "seq": self.next_sequence_number(glean, ping_name),
+            "start_time": start_time,
+            "end_time": end_time,
+        })
+    }
+
+    /// Glean's own sequence-number bookkeeping, recorded like any other metric
+    /// (into the reserved `glean_internal_info` ping) so it persists across
+    /// restarts without a bespoke storage format.
+    fn next_sequence_number(&self, glean: &Glean, ping_name: &str) -> u64 {
+        let meta = Self::bookkeeping_metric(ping_name, "seq");
+
+        let current = glean
+            .storage()
+            .get(GLEAN_INTERNAL_INFO_PING, Lifetime::User, &meta.fullname())
+            .and_then(|value| value.as_u64())
+            .unwrap_or(0);
+        let next = current + 1;
+        glean.storage().record(&meta, "counter", json!(next));
+        next
+    }
+
+    fn bookkeeping_metric(ping_name: &str, suffix: &str) -> CommonMetricData {
+        CommonMetricData {
+            name: format!("{}_{}", ping_name, suffix),
+            category: "glean.internal.pings".to_string(),
+            send_in_pings: vec![GLEAN_INTERNAL_INFO_PING.to_string()],
+            lifetime: Lifetime::User,
+            disabled: false,
+        }
+    }
+}
+
+/// The epoch-millisecond instant `ping_name`'s current collection period
+/// started, lazily initialized the first time it's asked for.
+///
+/// Event metrics use this to timestamp events relative to ping start.
+pub(crate) fn ping_start_time_millis(glean: &Glean, ping_name: &str) -> i64 {
+    let meta = PingMaker::bookkeeping_metric(ping_name, "start_time");
+
+    if let Some(existing) = glean
+        .storage()
+        .get(GLEAN_INTERNAL_INFO_PING, Lifetime::User, &meta.fullname())
+        .and_then(|value| value.as_i64())
+    {
+        return existing;
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    glean.storage().record(&meta, "counter", json!(now));
+    now
+}
+
+/// Reset `ping_name`'s start time to now, beginning a fresh collection period.
+///
+/// Called once a ping has been assembled and its `Ping`-lifetime data cleared.
+pub(crate) fn reset_ping_start_time(glean: &Glean, ping_name: &str) {
+    let meta = PingMaker::bookkeeping_metric(ping_name, "start_time");
+    glean
+        .storage()
+        .record(&meta, "counter", json!(chrono::Utc::now().timestamp_millis()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_glean, test_lock};
+
+    #[test]
+    fn collect_is_none_when_there_is_no_data() {
+        let _guard = test_lock();
+        let glean = test_glean();
+        glean.set_upload_enabled(true);
+
+        assert_eq!(PingMaker::new().collect(glean, "collect_empty_ping"), None);
+    }
+
+    #[test]
+    fn collect_is_none_when_upload_is_disabled() {
+        let _guard = test_lock();
+        let glean = test_glean();
+        glean.set_upload_enabled(true);
+
+        let meta = CommonMetricData {
+            name: "value".to_string(),
+            category: "test.ping".to_string(),
+            send_in_pings: vec!["collect_disabled_ping".to_string()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+        };
+        glean.storage().record(&meta, "counter", json!(1));
+
+        glean.set_upload_enabled(false);
+        assert_eq!(PingMaker::new().collect(glean, "collect_disabled_ping"), None);
+        glean.set_upload_enabled(true);
+    }
+
+    #[test]
+    fn collect_groups_metrics_by_type() {
+        let _guard = test_lock();
+        let glean = test_glean();
+        glean.set_upload_enabled(true);
+
+        let counter_meta = CommonMetricData {
+            name: "a_counter".to_string(),
+            category: "test.ping".to_string(),
+            send_in_pings: vec!["collect_grouping_ping".to_string()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+        };
+        let string_meta = CommonMetricData {
+            name: "a_string".to_string(),
+            category: "test.ping".to_string(),
+            send_in_pings: vec!["collect_grouping_ping".to_string()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+        };
+        glean.storage().record(&counter_meta, "counter", json!(7));
+        glean.storage().record(&string_meta, "string", json!("hello"));
+
+        let payload = PingMaker::new()
+            .collect(glean, "collect_grouping_ping")
+            .expect("there is data to collect");
+
+        assert_eq!(payload["metrics"]["counter"]["test.ping.a_counter"], json!(7));
+        assert_eq!(payload["metrics"]["string"]["test.ping.a_string"], json!("hello"));
+    }
+
+    #[test]
+    fn submit_ping_clears_ping_lifetime_data_and_enqueues_for_upload() {
+        let _guard = test_lock();
+        let glean = test_glean();
+        glean.set_upload_enabled(true);
+
+        let meta = CommonMetricData {
+            name: "submitted".to_string(),
+            category: "test.ping".to_string(),
+            send_in_pings: vec!["submit_ping_test".to_string()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+        };
+        glean.storage().record(&meta, "counter", json!(1));
+
+        assert!(glean.submit_ping("submit_ping_test"));
+        assert_eq!(glean.storage().get("submit_ping_test", Lifetime::Ping, &meta.fullname()), None);
+
+        let queued = glean.upload_queue().drain();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].ping_name, "submit_ping_test");
+
+        // Nothing left to send: a second submission is a no-op.
+        assert!(!glean.submit_ping("submit_ping_test"));
+    }
+
+    #[test]
+    fn submit_ping_end_time_is_never_before_start_time() {
+        let _guard = test_lock();
+        let glean = test_glean();
+        glean.set_upload_enabled(true);
+
+        let meta = CommonMetricData {
+            name: "timed".to_string(),
+            category: "test.ping".to_string(),
+            send_in_pings: vec!["submit_ping_timing".to_string()],
+            lifetime: Lifetime::Ping,
+            disabled: false,
+        };
+
+        glean.storage().record(&meta, "counter", json!(1));
+        assert!(glean.submit_ping("submit_ping_timing"));
+        glean.storage().record(&meta, "counter", json!(1));
+        assert!(glean.submit_ping("submit_ping_timing"));
+
+        for queued in glean.upload_queue().drain() {
+            let info = &queued.payload["ping_info"];
+            let start_time = chrono::DateTime::parse_from_rfc3339(info["start_time"].as_str().unwrap()).unwrap();
+            let end_time = chrono::DateTime::parse_from_rfc3339(info["end_time"].as_str().unwrap()).unwrap();
+            assert!(end_time >= start_time);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Loading metric definitions from a `metrics.yaml` registry.
+//!
+//! This lets an application declare its metrics declaratively, rather than
+//! hand-constructing a [`CommonMetricData`] for each one, while still landing
+//! on the same `category.name` fullname scheme [`CommonMetricData::fullname`]
+//! already assumes.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::{CommonMetricData, Lifetime};
+
+/// The kind of metric a registry entry declares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    String,
+    Event,
+}
+
+impl MetricType {
+    fn parse(raw: &str) -> Result<Self, RegistryError> {
+        match raw {
+            "counter" => Ok(MetricType::Counter),
+            "string" => Ok(MetricType::String),
+            "event" => Ok(MetricType::Event),
+            other => Err(RegistryError::UnknownMetricType(other.to_string())),
+        }
+    }
+}
+
+/// A single metric, as declared under a category in `metrics.yaml`.
+#[derive(Debug)]
+pub struct RegistryEntry {
+    pub metric_type: MetricType,
+    pub meta: CommonMetricData,
+    /// The extra keys an event metric is allowed to record, as passed to
+    /// [`crate::metrics::EventMetric::new`]. Empty for every other metric type.
+    pub extra_keys: Vec<String>,
+}
+
+/// Everything that can go wrong while loading a `metrics.yaml` registry.
+#[derive(Debug)]
+pub enum RegistryError {
+    /// The document isn't valid YAML, or doesn't match the expected shape.
+    Malformed(serde_yaml::Error),
+    /// A metric declared a `type` this crate doesn't know how to build.
+    UnknownMetricType(String),
+    /// A metric declared a `lifetime` that isn't `ping`, `application`, or `user`.
+    UnknownLifetime(String),
+    /// A metric is sent into a ping that wasn't in the caller's `known_pings`.
+    UnknownPing { metric: String, ping: String },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Malformed(err) => write!(f, "malformed metrics.yaml: {}", err),
+            RegistryError::UnknownMetricType(ty) => write!(f, "unknown metric type '{}'", ty),
+            RegistryError::UnknownLifetime(lifetime) => write!(f, "unknown lifetime '{}'", lifetime),
+            RegistryError::UnknownPing { metric, ping } => {
+                write!(f, "metric '{}' is sent in unknown ping '{}'", metric, ping)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// The shape of a single metric entry in `metrics.yaml`, before it's turned
+/// into a [`CommonMetricData`].
+#[derive(Deserialize)]
+struct RawMetric {
+    #[serde(rename = "type")]
+    metric_type: String,
+    #[serde(default)]
+    lifetime: Option<String>,
+    #[serde(default)]
+    send_in_pings: Option<Vec<String>>,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default)]
+    extra_keys: Option<Vec<String>>,
+}
+
+fn parse_lifetime(raw: Option<&str>) -> Result<Lifetime, RegistryError> {
+    match raw {
+        None | Some("ping") => Ok(Lifetime::Ping),
+        Some("application") => Ok(Lifetime::Application),
+        Some("user") => Ok(Lifetime::User),
+        Some(other) => Err(RegistryError::UnknownLifetime(other.to_string())),
+    }
+}
+
+/// Parse a `metrics.yaml` document into a registry of metric definitions.
+///
+/// `known_pings` lists every ping name the application has declared; a
+/// metric naming a ping outside that set is rejected rather than silently
+/// accepted. A metric with no `send_in_pings` defaults to `["metrics"]`
+/// (unlike `CommonMetricData::default()`, whose `send_in_pings` is empty),
+/// and one with no `lifetime` defaults to [`Lifetime::Ping`]. An event
+/// metric with no `extra_keys` defaults to an empty allowed set, rejecting
+/// every extra key.
+pub fn load_registry(yaml: &str, known_pings: &[&str]) -> Result<Vec<RegistryEntry>, RegistryError> {
+    let raw: HashMap<String, HashMap<String, RawMetric>> =
+        serde_yaml::from_str(yaml).map_err(RegistryError::Malformed)?;
+
+    let mut entries = Vec::new();
+    for (category, metrics) in raw {
+        for (name, raw_metric) in metrics {
+            let metric_type = MetricType::parse(&raw_metric.metric_type)?;
+            let lifetime = parse_lifetime(raw_metric.lifetime.as_deref())?;
+            let send_in_pings = raw_metric
+                .send_in_pings
+                .unwrap_or_else(|| vec!["metrics".to_string()]);
+
+            for ping in &send_in_pings {
+                if !known_pings.contains(&ping.as_str()) {
+                    return Err(RegistryError::UnknownPing {
+                        metric: format!("{}.{}", category, name),
+                        ping: ping.clone(),
+                    });
+                }
+            }
+
+            let extra_keys = match metric_type {
+                MetricType::Event => raw_metric.extra_keys.unwrap_or_default(),
+                _ => Vec::new(),
+            };
+
+            entries.push(RegistryEntry {
+                metric_type,
+                meta: CommonMetricData {
+                    name,
+                    category: category.clone(),
+                    send_in_pings,
+                    lifetime,
+                    disabled: raw_metric.disabled,
+                },
+                extra_keys,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn well_formed_doc_produces_the_right_common_metric_data() {
+        let yaml = r#"
+test.registry:
+  a_counter:
+    type: counter
+    lifetime: user
+    send_in_pings: ["metrics", "baseline"]
+    disabled: true
+"#;
+        let entries = load_registry(yaml, &["metrics", "baseline"]).expect("valid registry");
+
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.metric_type, MetricType::Counter);
+        assert_eq!(entry.meta.fullname(), "test.registry.a_counter");
+        assert_eq!(entry.meta.lifetime, Lifetime::User);
+        assert_eq!(entry.meta.send_in_pings, vec!["metrics".to_string(), "baseline".to_string()]);
+        assert!(entry.meta.disabled);
+        assert!(entry.extra_keys.is_empty());
+    }
+
+    #[test]
+    fn lifetime_and_send_in_pings_default_when_omitted() {
+        let yaml = r#"
+test.registry:
+  a_string:
+    type: string
+"#;
+        let entries = load_registry(yaml, &["metrics"]).expect("valid registry");
+
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.meta.lifetime, Lifetime::Ping);
+        assert_eq!(entry.meta.send_in_pings, vec!["metrics".to_string()]);
+        assert!(!entry.meta.disabled);
+    }
+
+    #[test]
+    fn event_extra_keys_are_carried_through() {
+        let yaml = r#"
+test.registry:
+  an_event:
+    type: event
+    extra_keys: ["button"]
+"#;
+        let entries = load_registry(yaml, &["metrics"]).expect("valid registry");
+
+        assert_eq!(entries[0].extra_keys, vec!["button".to_string()]);
+    }
+
+    #[test]
+    fn unknown_ping_is_rejected() {
+        let yaml = r#"
+test.registry:
+  a_counter:
+    type: counter
+    send_in_pings: ["not_a_known_ping"]
+"#;
+        let err = load_registry(yaml, &["metrics"]).expect_err("unknown ping should be rejected");
+
+        match err {
+            RegistryError::UnknownPing { metric, ping } => {
+                assert_eq!(metric, "test.registry.a_counter");
+                assert_eq!(ping, "not_a_known_ping");
+            }
+            other => panic!("expected UnknownPing, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_metric_type_is_rejected() {
+        let yaml = r#"
+test.registry:
+  a_thing:
+    type: not_a_real_type
+"#;
+        let err = load_registry(yaml, &["metrics"]).expect_err("unknown metric type should be rejected");
+
+        assert!(matches!(err, RegistryError::UnknownMetricType(ty) if ty == "not_a_real_type"));
+    }
+
+    #[test]
+    fn unknown_lifetime_is_rejected() {
+        let yaml = r#"
+test.registry:
+  a_counter:
+    type: counter
+    lifetime: not_a_real_lifetime
+"#;
+        let err = load_registry(yaml, &["metrics"]).expect_err("unknown lifetime should be rejected");
+
+        assert!(matches!(err, RegistryError::UnknownLifetime(lifetime) if lifetime == "not_a_real_lifetime"));
+    }
+
+    #[test]
+    fn malformed_yaml_is_rejected() {
+        let err = load_registry("not: [valid, metrics.yaml", &["metrics"]).expect_err("malformed yaml should be rejected");
+
+        assert!(matches!(err, RegistryError::Malformed(_)));
+    }
+}
@@ -0,0 +1,222 @@
+//! Lifetime-aware persistent storage for recorded metric values.
+//!
+//! Values are namespaced by ping name, so the same metric recorded into
+//! multiple `send_in_pings` destinations keeps independent state per ping,
+//! and further namespaced by [`Lifetime`], so the three lifetimes never
+//! collide with each other even for the same metric and ping.
+
+mod persistence;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde_json::Value as JsonValue;
+
+use persistence::StoredRecord;
+
+use crate::{CommonMetricData, Lifetime};
+
+/// A stored metric value together with the metric type that produced it.
+///
+/// The type tag is what lets [`crate::ping::PingMaker`] group a ping's
+/// metrics into the right section of the payload without needing to consult
+/// a metric registry at assembly time.
+#[derive(Clone)]
+pub struct StoredValue {
+    pub metric_type: String,
+    pub value: JsonValue,
+}
+
+/// Keyed, lifetime-aware storage for metric values.
+///
+/// Keys have the shape `<ping_name>#<lifetime>#<category.name>`. `User`-lifetime
+/// values survive application restarts and are only wiped by [`StorageManager::clear_all`];
+/// `Application`-lifetime values live only in memory and are never written to disk;
+/// `Ping`-lifetime values are persisted until the owning ping is submitted, at which
+/// point [`StorageManager::clear_ping`] removes them.
+///
+/// Durable entries are each bincode-encoded into their own file, so a crash
+/// mid-write only risks the one record being written, and a record from a
+/// future, incompatible version of the crate is dropped on load rather than
+/// taking the whole store down with it.
+pub struct StorageManager {
+    data_dir: PathBuf,
+    data: Mutex<HashMap<String, StoredValue>>,
+}
+
+impl StorageManager {
+    /// Open (or create) the storage backend rooted at `data_dir`.
+    pub fn new(data_dir: impl Into<PathBuf>) -> Self {
+        let data_dir = data_dir.into();
+        let data = persistence::read_all_records(&data_dir)
+            .into_iter()
+            .map(|record| {
+                (
+                    record.key,
+                    StoredValue {
+                        metric_type: record.metric_type,
+                        value: record.value,
+                    },
+                )
+            })
+            .collect();
+        StorageManager {
+            data_dir,
+            data: Mutex::new(data),
+        }
+    }
+
+    fn store_key(ping_name: &str, lifetime: Lifetime, fullname: &str) -> String {
+        format!("{}#{}#{}", ping_name, lifetime.as_str(), fullname)
+    }
+
+    /// Record `value`, produced by a metric of kind `metric_type`, for `meta`,
+    /// into every ping it is sent in.
+    pub fn record(&self, meta: &CommonMetricData, metric_type: &str, value: JsonValue) {
+        for ping_name in meta.storage_names() {
+            self.record_one(ping_name, meta, metric_type, value.clone());
+        }
+    }
+
+    /// Record `value` for `meta` into a single named ping, rather than every
+    /// ping it is sent in.
+    ///
+    /// Metric types whose pings accumulate independent state (e.g. a counter
+    /// that increments per-ping, or an event list that appends per-ping) use
+    /// this instead of [`StorageManager::record`].
+    pub fn record_one(&self, ping_name: &str, meta: &CommonMetricData, metric_type: &str, value: JsonValue) {
+        let fullname = meta.fullname();
+        let key = Self::store_key(ping_name, meta.lifetime, &fullname);
+
+        {
+            let mut data = self.data.lock().unwrap();
+            data.insert(
+                key.clone(),
+                StoredValue {
+                    metric_type: metric_type.to_string(),
+                    value: value.clone(),
+                },
+            );
+        }
+
+        if meta.lifetime != Lifetime::Application {
+            let record = StoredRecord {
+                key: key.clone(),
+                fullname,
+                lifetime: meta.lifetime,
+                metric_type: metric_type.to_string(),
+                value,
+            };
+            let _ = persistence::write_record(&persistence::record_path(&self.data_dir, &key), &record);
+        }
+    }
+
+    /// Look up the current value of `fullname` within `ping_name`, if any.
+    pub fn get(&self, ping_name: &str, lifetime: Lifetime, fullname: &str) -> Option<JsonValue> {
+        let key = Self::store_key(ping_name, lifetime, fullname);
+        self.data.lock().unwrap().get(&key).map(|stored| stored.value.clone())
+    }
+
+    /// All entries currently stored for `ping_name`, keyed by `<lifetime>#<category.name>`.
+    pub fn snapshot(&self, ping_name: &str) -> HashMap<String, StoredValue> {
+        let prefix = format!("{}#", ping_name);
+        self.data
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, value)| (key[prefix.len()..].to_string(), value.clone()))
+            .collect()
+    }
+
+    /// Clear all `Ping`-lifetime values stored for `ping_name`.
+    ///
+    /// Called once a ping has been assembled and queued for upload.
+    pub fn clear_ping(&self, ping_name: &str) {
+        let prefix = Self::store_key(ping_name, Lifetime::Ping, "");
+        let mut data = self.data.lock().unwrap();
+        let keys: Vec<String> = data.keys().filter(|key| key.starts_with(&prefix)).cloned().collect();
+        for key in keys {
+            data.remove(&key);
+            let _ = fs::remove_file(persistence::record_path(&self.data_dir, &key));
+        }
+    }
+
+    /// Clear every `Application`-lifetime value across all pings.
+    pub fn clear_application_lifetime(&self) {
+        let marker = format!("#{}#", Lifetime::Application.as_str());
+        self.data.lock().unwrap().retain(|key, _| !key.contains(&marker));
+    }
+
+    /// Wipe every stored value, regardless of lifetime. Used when the user profile is reset.
+    pub fn clear_all(&self) {
+        self.data.lock().unwrap().clear();
+        let _ = fs::remove_dir_all(&self.data_dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("glean_core_storage_test_{}_{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn user_lifetime_values_survive_a_reload() {
+        let dir = temp_dir("user_lifetime_reload");
+        let _ = fs::remove_dir_all(&dir);
+
+        let meta = CommonMetricData {
+            name: "persisted".to_string(),
+            category: "test.storage".to_string(),
+            send_in_pings: vec!["metrics".to_string()],
+            lifetime: Lifetime::User,
+            disabled: false,
+        };
+
+        {
+            let storage = StorageManager::new(&dir);
+            storage.record(&meta, "string", json!("hello"));
+        }
+
+        let reloaded = StorageManager::new(&dir);
+        assert_eq!(
+            reloaded.get("metrics", Lifetime::User, &meta.fullname()),
+            Some(json!("hello"))
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn application_lifetime_values_do_not_survive_a_reload() {
+        let dir = temp_dir("application_lifetime_reload");
+        let _ = fs::remove_dir_all(&dir);
+
+        let meta = CommonMetricData {
+            name: "not_persisted".to_string(),
+            category: "test.storage".to_string(),
+            send_in_pings: vec!["metrics".to_string()],
+            lifetime: Lifetime::Application,
+            disabled: false,
+        };
+
+        {
+            let storage = StorageManager::new(&dir);
+            storage.record(&meta, "string", json!("ephemeral"));
+        }
+
+        let reloaded = StorageManager::new(&dir);
+        assert_eq!(
+            reloaded.get("metrics", Lifetime::Application, &meta.fullname()),
+            None
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
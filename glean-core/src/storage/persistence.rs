@@ -0,0 +1,97 @@
+//! Crash-safe, bincode-backed encoding for a single durable metric value.
+//!
+//! Each entry lives in its own file, written via a `.tmp` file plus a rename
+//! so a crash mid-write never leaves a partially-written record behind. The
+//! on-disk format is expected to evolve across versions of the crate, so a
+//! record that fails to decode is dropped rather than treated as fatal.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::Lifetime;
+
+/// A single durable metric value, tagged with the metadata needed to match it
+/// back up against a metric registry loaded at startup.
+pub(crate) struct StoredRecord {
+    pub key: String,
+    pub fullname: String,
+    pub lifetime: Lifetime,
+    pub metric_type: String,
+    pub value: JsonValue,
+}
+
+/// The bincode-friendly shape [`StoredRecord`] is actually encoded as.
+///
+/// `serde_json::Value`'s `Deserialize` impl requires `deserialize_any`, which
+/// bincode does not support, so the JSON payload is carried as a pre-encoded
+/// byte blob rather than as a `JsonValue` field.
+#[derive(Serialize, Deserialize)]
+struct WireRecord {
+    key: String,
+    fullname: String,
+    lifetime: Lifetime,
+    metric_type: String,
+    value: Vec<u8>,
+}
+
+/// The file a given storage `key` is persisted under within `dir`.
+///
+/// Keys can contain characters that aren't safe in file names (`.`, `#`), so
+/// the key is hashed rather than used as the file name directly; the key
+/// itself still round-trips through the record contents.
+pub(crate) fn record_path(dir: &Path, key: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    dir.join(format!("{:016x}.bin", hasher.finish()))
+}
+
+/// Durably write `record` to `path`, replacing whatever was there before.
+pub(crate) fn write_record(path: &Path, record: &StoredRecord) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let wire = WireRecord {
+        key: record.key.clone(),
+        fullname: record.fullname.clone(),
+        lifetime: record.lifetime,
+        metric_type: record.metric_type.clone(),
+        value: serde_json::to_vec(&record.value).map_err(std::io::Error::other)?,
+    };
+    let encoded = bincode::serialize(&wire).map_err(std::io::Error::other)?;
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, encoded)?;
+    fs::rename(&tmp_path, path)
+}
+
+/// Read every record persisted under `dir`, silently dropping any file that
+/// cannot be decoded as a [`StoredRecord`] (for instance one written by an
+/// older, incompatible version of the crate) instead of panicking.
+pub(crate) fn read_all_records(dir: &Path) -> Vec<StoredRecord> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "bin"))
+        .filter_map(|entry| fs::read(entry.path()).ok())
+        .filter_map(|bytes| bincode::deserialize::<WireRecord>(&bytes).ok())
+        .filter_map(|wire| {
+            let value = serde_json::from_slice(&wire.value).ok()?;
+            Some(StoredRecord {
+                key: wire.key,
+                fullname: wire.fullname,
+                lifetime: wire.lifetime,
+                metric_type: wire.metric_type,
+                value,
+            })
+        })
+        .collect()
+}
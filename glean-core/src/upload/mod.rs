@@ -0,0 +1,69 @@
+//! In-memory queue of assembled pings awaiting upload.
+//!
+//! `glean-core` only assembles and queues ping payloads; actually sending
+//! them over the network is the job of the platform-specific uploader, which
+//! drains this queue on its own schedule.
+
+use std::sync::Mutex;
+
+use serde_json::Value as JsonValue;
+
+/// A single ping ready to be uploaded.
+pub struct PingRequest {
+    /// The UUID Glean generated for this submission.
+    pub document_id: String,
+    /// The ping's name, e.g. `"metrics"` or `"events"`.
+    pub ping_name: String,
+    /// The fully assembled JSON ping payload.
+    pub payload: JsonValue,
+}
+
+/// Holds assembled pings until the platform-specific uploader drains them.
+#[derive(Default)]
+pub struct UploadManager {
+    queue: Mutex<Vec<PingRequest>>,
+}
+
+impl UploadManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue `request` for upload.
+    pub fn enqueue(&self, request: PingRequest) {
+        self.queue.lock().unwrap().push(request);
+    }
+
+    /// Remove and return every currently queued ping request, in submission order.
+    pub fn drain(&self) -> Vec<PingRequest> {
+        std::mem::take(&mut *self.queue.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn drain_returns_requests_in_submission_order_and_empties_the_queue() {
+        let upload = UploadManager::new();
+        upload.enqueue(PingRequest {
+            document_id: "first".to_string(),
+            ping_name: "metrics".to_string(),
+            payload: json!({}),
+        });
+        upload.enqueue(PingRequest {
+            document_id: "second".to_string(),
+            ping_name: "metrics".to_string(),
+            payload: json!({}),
+        });
+
+        let drained = upload.drain();
+        assert_eq!(drained.len(), 2);
+        assert_eq!(drained[0].document_id, "first");
+        assert_eq!(drained[1].document_id, "second");
+
+        assert!(upload.drain().is_empty());
+    }
+}